@@ -25,6 +25,41 @@ pub struct CommonArgs {
 
     #[clap(long)]
     pub out_file: PathBuf,
+
+    #[clap(long, value_enum, default_value = "csv")]
+    pub output_format: OutputFormat,
+
+    /// How to emit the end-of-run summary (total duration, achieved vs requested rate,
+    /// per-operation-type counts/errors and latency percentiles).
+    #[clap(long, value_enum, default_value = "human")]
+    pub summary_format: SummaryFormat,
+
+    /// Where to write the summary when `--summary-format` is `json` or `csv`. Defaults to
+    /// stdout if not given.
+    #[clap(long)]
+    pub summary_file: Option<PathBuf>,
+}
+
+/// Which structured output sink(s) to write completed operations to.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// One CSV row per operation, at `out_file`.
+    Csv,
+    /// One JSON object per line, at `out_file` with its extension replaced by `.jsonl`.
+    Jsonl,
+    /// Both of the above.
+    Both,
+}
+
+/// Which format to emit the end-of-run summary document in.
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum SummaryFormat {
+    /// The existing human-readable summary printed to stdout.
+    Human,
+    /// A single JSON document.
+    Json,
+    /// A single CSV row per operation type.
+    Csv,
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -0,0 +1,53 @@
+/// How many bits of mantissa precision to keep within each power-of-two magnitude. Latencies
+/// within a bucket are indistinguishable, giving at most `2^-SIGNIFICANT_BITS` (~0.8%) relative
+/// error per recorded sample while keeping the number of buckets, and so memory use, bounded to
+/// `O(2^SIGNIFICANT_BITS * log2(max_value))` rather than growing with the number of samples.
+const SIGNIFICANT_BITS: u32 = 7;
+
+/// Rounds `value` down to the representative value of the bucket it falls into: below
+/// `2^SIGNIFICANT_BITS` every value gets its own bucket, above that buckets are spaced linearly
+/// within each power-of-two magnitude.
+fn bucket_start(value: u64) -> u64 {
+    if value < (1 << SIGNIFICANT_BITS) {
+        return value;
+    }
+    let magnitude = 63 - value.leading_zeros();
+    let shift = magnitude - SIGNIFICANT_BITS;
+    (value >> shift) << shift
+}
+
+/// A high-dynamic-range latency histogram: records in `O(1)` and constant memory regardless of
+/// sample count, at the cost of `2^-SIGNIFICANT_BITS` relative precision, so arbitrary quantiles
+/// (including deep into the tail) can be queried after the fact without keeping every sample.
+#[derive(Debug, Default, Clone)]
+pub struct LatencyHistogram {
+    counts: std::collections::BTreeMap<u64, u64>,
+    total: u64,
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, value_us: u64) {
+        *self.counts.entry(bucket_start(value_us)).or_default() += 1;
+        self.total += 1;
+    }
+
+    /// The `p`th quantile (`0.0..=1.0`) of recorded values, or `0` if nothing was recorded.
+    pub fn quantile(&self, p: f64) -> u64 {
+        if self.total == 0 {
+            return 0;
+        }
+        let target = (((self.total - 1) as f64) * p).round() as u64;
+        let mut seen = 0;
+        for (&bucket, &count) in &self.counts {
+            seen += count;
+            if seen > target {
+                return bucket;
+            }
+        }
+        self.counts.keys().next_back().copied().unwrap_or(0)
+    }
+
+    pub fn max(&self) -> u64 {
+        self.counts.keys().next_back().copied().unwrap_or(0)
+    }
+}
@@ -1,7 +1,11 @@
 use std::fs::File;
+use std::io::Write;
 
+use crate::output::JsonlOutputSink;
+use crate::output::SummaryOutputSink;
 use crate::ycsb::YcsbDispatcherGenerator;
 use crate::ycsb::YcsbInputGenerator;
+use crate::ycsb::YcsbOutput;
 use loadbench::output_sink::CsvOutputSink;
 use loadbench::output_sink::OutputSink;
 use loadbench::output_sink::StatsOutputSink;
@@ -9,6 +13,8 @@ use rand::rngs::StdRng;
 use rand::SeedableRng;
 
 pub mod args;
+pub mod histogram;
+pub mod output;
 pub mod protos;
 pub mod ycsb;
 
@@ -24,41 +30,69 @@ pub async fn run_ycsb(args: args::CommonArgs, ycsb_args: crate::ycsb::Args) {
         scan_weight: ycsb_args.scan_weight,
         insert_weight: ycsb_args.insert_weight,
         update_weight: ycsb_args.update_weight,
+        watch_weight: ycsb_args.watch_weight,
+        verify_weight: ycsb_args.verify_weight,
+        txn_weight: ycsb_args.txn_weight,
+        txn_size: ycsb_args.txn_size,
+        txn_read_ratio: ycsb_args.txn_read_ratio,
         fields_per_record: ycsb_args.fields_per_record,
         field_value_length: ycsb_args.field_value_length,
         operation_rng: StdRng::from_entropy(),
         max_record_index: ycsb_args.max_record_index,
         max_scan_length: ycsb_args.max_scan_length,
         request_distribution: ycsb_args.request_distribution,
+        zipfian: crate::ycsb::ZipfianGenerator::new(ycsb_args.zipfian_theta),
     };
 
-    let dispatcher_generator = YcsbDispatcherGenerator::new(args.endpoint, &args.common_dir).await;
+    let dispatcher_generator = YcsbDispatcherGenerator::new(
+        args.endpoint,
+        &args.common_dir,
+        ycsb_args.watchers,
+        ycsb_args.txn_abort_on_conflict,
+    )
+    .await;
 
-    struct DoubleOutputSink {
+    struct CombinedOutputSink {
         stats: StatsOutputSink,
-        csv: CsvOutputSink<File>,
+        csv: Option<CsvOutputSink<File>>,
+        jsonl: Option<JsonlOutputSink<File>>,
+        summary: SummaryOutputSink,
     }
 
-    let stats_output_sink = StatsOutputSink::default();
+    let write_csv = matches!(
+        args.output_format,
+        args::OutputFormat::Csv | args::OutputFormat::Both
+    );
+    let write_jsonl = matches!(
+        args.output_format,
+        args::OutputFormat::Jsonl | args::OutputFormat::Both
+    );
 
-    let writer = csv::Writer::from_path(args.out_file).unwrap();
-    let csv_output_sink = CsvOutputSink { writer };
+    let csv = write_csv.then(|| CsvOutputSink {
+        writer: csv::Writer::from_path(&args.out_file).unwrap(),
+    });
+    let jsonl = write_jsonl.then(|| JsonlOutputSink {
+        writer: File::create(args.out_file.with_extension("jsonl")).unwrap(),
+    });
 
-    let mut output_sink = DoubleOutputSink {
-        stats: stats_output_sink,
-        csv: csv_output_sink,
+    let mut output_sink = CombinedOutputSink {
+        stats: StatsOutputSink::default(),
+        csv,
+        jsonl,
+        summary: SummaryOutputSink::new(ycsb_args.rate),
     };
 
     #[async_trait::async_trait]
-    impl<O> OutputSink<O> for DoubleOutputSink
-    where
-        O: Clone + Send + serde::Serialize + 'static,
-    {
-        async fn send(&mut self, output: loadbench::Output<O>) {
-            let o = output.clone();
-            self.stats.send(o).await;
-            let o = output.clone();
-            self.csv.send(o).await;
+    impl OutputSink<YcsbOutput> for CombinedOutputSink {
+        async fn send(&mut self, output: loadbench::Output<YcsbOutput>) {
+            self.stats.send(output.clone()).await;
+            if let Some(csv) = &mut self.csv {
+                csv.send(output.clone()).await;
+            }
+            if let Some(jsonl) = &mut self.jsonl {
+                jsonl.send(output.clone()).await;
+            }
+            self.summary.send(output).await;
         }
     }
 
@@ -74,4 +108,28 @@ pub async fn run_ycsb(args: args::CommonArgs, ycsb_args: crate::ycsb::Args) {
     .await;
 
     output_sink.stats.summary();
+
+    match args.summary_format {
+        args::SummaryFormat::Human => {}
+        args::SummaryFormat::Json => {
+            let summary = output_sink.summary.summary();
+            let json = serde_json::to_string_pretty(&summary).unwrap();
+            write_summary(&args.summary_file, &json);
+        }
+        args::SummaryFormat::Csv => {
+            let summary = output_sink.summary.summary();
+            write_summary(&args.summary_file, &summary.to_csv());
+        }
+    }
+}
+
+fn write_summary(summary_file: &Option<std::path::PathBuf>, contents: &str) {
+    match summary_file {
+        Some(path) => {
+            File::create(path)
+                .and_then(|mut f| f.write_all(contents.as_bytes()))
+                .unwrap();
+        }
+        None => println!("{contents}"),
+    }
 }
@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use loadbench::output_sink::OutputSink;
+use loadbench::Output;
+use serde::Serialize;
+
+use crate::histogram::LatencyHistogram;
+use crate::ycsb::YcsbOutput;
+
+/// Writes one JSON object per completed operation to `writer`, in the same spirit as
+/// `loadbench::output_sink::CsvOutputSink` but without the CSV schema coupling: downstream
+/// tooling can stream-parse the file without knowing the column layout up front, and errors are
+/// kept as first-class fields rather than dropped.
+pub struct JsonlOutputSink<W> {
+    pub writer: W,
+}
+
+#[async_trait]
+impl<O, W> OutputSink<O> for JsonlOutputSink<W>
+where
+    O: Serialize + Send + 'static,
+    W: Write + Send,
+{
+    async fn send(&mut self, output: Output<O>) {
+        if let Err(err) = serde_json::to_writer(&mut self.writer, &output) {
+            eprintln!("failed to serialize jsonl output: {err}");
+            return;
+        }
+        if let Err(err) = writeln!(self.writer) {
+            eprintln!("failed to write jsonl output: {err}");
+        }
+    }
+}
+
+#[derive(Default)]
+struct OperationCounters {
+    count: u64,
+    errors: u64,
+    histogram: LatencyHistogram,
+}
+
+/// Collects total duration, achieved rate and per-operation-type counts/errors/latency
+/// histograms as a run progresses, so they can be emitted as a structured document instead of
+/// only the stdout printout `loadbench::output_sink::StatsOutputSink::summary()` gives today.
+///
+/// Latencies are recorded with coordinated-omission correction: `loadbench::generate_load`
+/// drives the target at a fixed `requested_rate`, so a stall that delays one dispatch also
+/// delays every request queued behind it, and recording only the measured service time of the
+/// request that happened to observe the stall would under-report how bad the tail really was.
+/// For every completed operation whose service time exceeds the `1/requested_rate`
+/// inter-arrival interval, we additionally record the latencies the omitted, queued-up requests
+/// would have observed, backfilled starting one inter-arrival interval below the latency the
+/// stalled request itself measured and stepping down by one interval while at least one full
+/// interval remains.
+pub struct SummaryOutputSink {
+    requested_rate: u64,
+    start: Instant,
+    total: u64,
+    by_operation: BTreeMap<String, OperationCounters>,
+}
+
+impl SummaryOutputSink {
+    pub fn new(requested_rate: u64) -> Self {
+        Self {
+            requested_rate,
+            start: Instant::now(),
+            total: 0,
+            by_operation: BTreeMap::new(),
+        }
+    }
+
+    pub fn summary(&self) -> Summary {
+        let elapsed = self.start.elapsed();
+        let operations = self
+            .by_operation
+            .iter()
+            .map(|(operation, counters)| OperationSummary {
+                operation: operation.clone(),
+                count: counters.count,
+                errors: counters.errors,
+                latency_p50_us: counters.histogram.quantile(0.50),
+                latency_p90_us: counters.histogram.quantile(0.90),
+                latency_p99_us: counters.histogram.quantile(0.99),
+                latency_p999_us: counters.histogram.quantile(0.999),
+                latency_max_us: counters.histogram.max(),
+            })
+            .collect();
+        Summary {
+            duration_ms: elapsed.as_millis(),
+            requested_rate: self.requested_rate,
+            achieved_rate: self.total as f64 / elapsed.as_secs_f64(),
+            operations,
+        }
+    }
+}
+
+#[async_trait]
+impl OutputSink<YcsbOutput> for SummaryOutputSink {
+    async fn send(&mut self, output: Output<YcsbOutput>) {
+        self.total += 1;
+        let latency_us = output.latency.as_micros() as u64;
+        let is_err = output.result.is_err();
+        // `YcsbDispatcher::execute` tags every error with the operation that produced it as an
+        // `"{operation}: {message}"` prefix, so a failed op is still bucketed under its own
+        // operation type instead of an undifferentiated `"error"` total.
+        let operation = match &output.result {
+            Ok(output) => output.operation().to_owned(),
+            Err(err) => err
+                .split_once(": ")
+                .map_or_else(|| "error".to_owned(), |(operation, _)| operation.to_owned()),
+        };
+        let counters = self.by_operation.entry(operation).or_default();
+        counters.count += 1;
+        if is_err {
+            counters.errors += 1;
+        }
+
+        counters.histogram.record(latency_us);
+        // Mirrors HdrHistogram's `recordValueWithExpectedInterval`: only backfill while the
+        // remaining gap is itself at least one full inter-arrival interval, and skip entirely
+        // when the interval rounds down to 0 (requested_rate > 1_000_000/s, which would
+        // otherwise never advance the loop below).
+        if self.requested_rate > 0 {
+            let inter_arrival_us = 1_000_000 / self.requested_rate;
+            if inter_arrival_us > 0 && latency_us > inter_arrival_us {
+                let mut missing = latency_us - inter_arrival_us;
+                while missing >= inter_arrival_us {
+                    counters.histogram.record(missing);
+                    missing -= inter_arrival_us;
+                }
+            }
+        }
+    }
+}
+
+/// A machine-readable end-of-run summary: total duration, achieved vs requested rate and
+/// per-operation-type counts/errors/latency percentiles.
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub duration_ms: u128,
+    pub requested_rate: u64,
+    pub achieved_rate: f64,
+    pub operations: Vec<OperationSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationSummary {
+    pub operation: String,
+    pub count: u64,
+    pub errors: u64,
+    pub latency_p50_us: u64,
+    pub latency_p90_us: u64,
+    pub latency_p99_us: u64,
+    pub latency_p999_us: u64,
+    pub latency_max_us: u64,
+}
+
+impl Summary {
+    /// Denormalizes to one CSV row per operation type, repeating the run-level totals on each
+    /// row, in the same spirit as how `experiments::ycsb::analyse` cross-joins per-configuration
+    /// totals onto per-sample rows.
+    pub fn to_csv(&self) -> String {
+        #[derive(Serialize)]
+        struct Row<'a> {
+            duration_ms: u128,
+            requested_rate: u64,
+            achieved_rate: f64,
+            operation: &'a str,
+            count: u64,
+            errors: u64,
+            latency_p50_us: u64,
+            latency_p90_us: u64,
+            latency_p99_us: u64,
+            latency_p999_us: u64,
+            latency_max_us: u64,
+        }
+
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        for op in &self.operations {
+            writer
+                .serialize(Row {
+                    duration_ms: self.duration_ms,
+                    requested_rate: self.requested_rate,
+                    achieved_rate: self.achieved_rate,
+                    operation: &op.operation,
+                    count: op.count,
+                    errors: op.errors,
+                    latency_p50_us: op.latency_p50_us,
+                    latency_p90_us: op.latency_p90_us,
+                    latency_p99_us: op.latency_p99_us,
+                    latency_p999_us: op.latency_p999_us,
+                    latency_max_us: op.latency_max_us,
+                })
+                .unwrap();
+        }
+        String::from_utf8(writer.into_inner().unwrap()).unwrap()
+    }
+}
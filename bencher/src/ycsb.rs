@@ -1,16 +1,38 @@
+use std::collections::HashMap;
 use std::path::Path;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::time::Duration;
+use std::time::Instant;
 
+use crate::protos::etcdserverpb::compare::CompareResult;
+use crate::protos::etcdserverpb::compare::CompareTarget;
+use crate::protos::etcdserverpb::compare::TargetUnion;
 use crate::protos::etcdserverpb::kv_client::KvClient;
+use crate::protos::etcdserverpb::request_op::Request as RequestOpRequest;
+use crate::protos::etcdserverpb::watch_client::WatchClient;
+use crate::protos::etcdserverpb::watch_request::RequestUnion as WatchRequestUnion;
+use crate::protos::etcdserverpb::Compare;
 use crate::protos::etcdserverpb::PutRequest;
 use crate::protos::etcdserverpb::RangeRequest;
+use crate::protos::etcdserverpb::RequestOp;
+use crate::protos::etcdserverpb::TxnRequest;
+use crate::protos::etcdserverpb::WatchCreateRequest;
+use crate::protos::etcdserverpb::WatchRequest;
+use crate::protos::lskvserverpb::proof_element;
+use crate::protos::lskvserverpb::receipt_client::ReceiptClient;
+use crate::protos::lskvserverpb::GetReceiptRequest;
 use async_trait::async_trait;
+use futures_util::StreamExt;
 use loadbench::client::{Dispatcher, DispatcherGenerator};
 use loadbench::input::InputGenerator;
 use rand::{distributions::Alphanumeric, rngs::StdRng, Rng};
-use rand_distr::{Distribution, WeightedAliasIndex, Zipf};
+use rand_distr::{Distribution, WeightedAliasIndex};
 use serde::Deserialize;
 use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
 use tonic::transport::{Certificate, Channel, ClientTlsConfig, Identity};
 
 /// Generate inputs for the YCSB workloads.
@@ -20,24 +42,113 @@ pub struct YcsbInputGenerator {
     pub insert_weight: u32,
     pub update_weight: u32,
     pub rmw_weight: u32,
+    pub watch_weight: u32,
+    pub verify_weight: u32,
+    pub txn_weight: u32,
+    /// Number of keys bundled into each `Txn` op, split between reads and writes by
+    /// `txn_read_ratio`.
+    pub txn_size: u32,
+    /// Fraction (`0.0..=1.0`) of a `Txn` op's keys that are pure reads rather than
+    /// compare-and-write.
+    pub txn_read_ratio: f32,
     pub fields_per_record: u32,
     pub field_value_length: usize,
     pub operation_rng: StdRng,
     pub max_record_index: u32,
     pub max_scan_length: u32,
     pub request_distribution: RequestDistribution,
+    pub zipfian: ZipfianGenerator,
 }
 
 #[derive(Debug, Clone, Copy, clap::ValueEnum)]
 pub enum RequestDistribution {
     /// Uniformly over the existing keys.
     Uniform,
-    /// Weighted toward one end.
+    /// Weighted toward one end, following the reference YCSB zipfian distribution.
     Zipfian,
-    /// The last one available.
+    /// Zipfian, but with the drawn rank scrambled via an FNV hash so hot keys are spread across
+    /// the keyspace instead of clustered at the low end.
+    #[clap(name = "scrambled")]
+    ScrambledZipfian,
+    /// The most recently inserted records are the hottest, modelled as a zipfian distribution
+    /// over `max_record_index - rank`.
     Latest,
 }
 
+/// The reference YCSB zipfian generator: draws a rank in `[0, n)` weighted towards 0, with
+/// `theta` controlling the skew (0 is uniform, towards 1 is very skewed). `zetan` (the
+/// generalized harmonic number of `n` items) is cached and extended incrementally as `n` grows,
+/// so drawing from a growing keyspace stays `O(delta)` rather than `O(n)` per draw.
+#[derive(Debug, Clone)]
+pub struct ZipfianGenerator {
+    theta: f64,
+    alpha: f64,
+    zeta2theta: f64,
+    zetan: f64,
+    eta: f64,
+    count_for_zeta: u64,
+}
+
+impl ZipfianGenerator {
+    pub fn new(theta: f64) -> Self {
+        Self {
+            theta,
+            alpha: 1.0 / (1.0 - theta),
+            zeta2theta: zeta(0, 2, theta, 0.0),
+            zetan: 0.0,
+            eta: 0.0,
+            count_for_zeta: 0,
+        }
+    }
+
+    /// Draw a rank in `[0, n)`, recomputing `zetan`/`eta` if `n` has changed since the last draw.
+    pub fn next(&mut self, n: u64, rng: &mut impl Rng) -> u64 {
+        if n != self.count_for_zeta {
+            self.zetan = if n > self.count_for_zeta {
+                zeta(self.count_for_zeta, n, self.theta, self.zetan)
+            } else {
+                zeta(0, n, self.theta, 0.0)
+            };
+            self.count_for_zeta = n;
+            self.eta = (1.0 - (2.0 / n as f64).powf(1.0 - self.theta))
+                / (1.0 - self.zeta2theta / self.zetan);
+        }
+
+        let u: f64 = rng.gen();
+        let uz = u * self.zetan;
+        if uz < 1.0 {
+            return 0;
+        }
+        if uz < 1.0 + 0.5f64.powf(self.theta) {
+            return 1;
+        }
+        let rank = (n as f64 * (self.eta * u - self.eta + 1.0).powf(self.alpha)) as u64;
+        rank.min(n - 1)
+    }
+}
+
+/// `sum_{i=start+1..=end} 1/i^theta`, starting from `initial_sum` (the value of the same sum up
+/// to `start`). Used to both compute `zetan` from scratch and extend it incrementally.
+fn zeta(start: u64, end: u64, theta: f64, initial_sum: f64) -> f64 {
+    let mut sum = initial_sum;
+    for i in start..end {
+        sum += 1.0 / ((i + 1) as f64).powf(theta);
+    }
+    sum
+}
+
+/// Hash `x` through FNV-1a to scatter zipfian ranks across the keyspace.
+fn fnv1a64(x: u64) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    let mut hash = OFFSET_BASIS;
+    for byte in x.to_le_bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(PRIME);
+    }
+    hash
+}
+
 impl YcsbInputGenerator {
     pub fn new_record_key(&mut self) -> String {
         // TODO: may not want incremental inserts
@@ -50,15 +161,20 @@ impl YcsbInputGenerator {
             // a missing user
             return "user00000000".to_owned();
         }
+        let n = self.max_record_index as u64;
         let index = match self.request_distribution {
-            RequestDistribution::Zipfian => {
-                let s: f64 = self
-                    .operation_rng
-                    .sample(Zipf::new(self.max_record_index as u64, 1.5).unwrap());
-                1 + s.floor() as u32
+            RequestDistribution::Zipfian => 1 + self.zipfian.next(n, &mut self.operation_rng),
+            RequestDistribution::ScrambledZipfian => {
+                let rank = self.zipfian.next(n, &mut self.operation_rng);
+                1 + fnv1a64(rank) % n
+            }
+            RequestDistribution::Uniform => {
+                self.operation_rng.gen_range(1..=self.max_record_index) as u64
+            }
+            RequestDistribution::Latest => {
+                let rank = self.zipfian.next(n, &mut self.operation_rng);
+                n - rank
             }
-            RequestDistribution::Uniform => self.operation_rng.gen_range(1..=self.max_record_index),
-            RequestDistribution::Latest => self.max_record_index,
         };
         format!("user{:08}", index)
     }
@@ -104,6 +220,27 @@ pub enum YcsbInput {
     ReadAll { record_key: String },
     /// Scan records in order, starting at a randomly chosen key
     Scan { start_key: String, end_key: String },
+    /// Write a field that the run's persistent `--watchers` population is already watching; the
+    /// notification delay is recorded independently of this op by those long-lived streams, not
+    /// measured inline.
+    Watch {
+        record_key: String,
+        field_key: String,
+        field_value: String,
+    },
+    /// Read a committed field, fetch its ledger receipt and independently verify the Merkle
+    /// proof against the signed root.
+    Verify {
+        record_key: String,
+        field_key: String,
+    },
+    /// Bundle `txn_size` keys into a single txn: `read_keys` are fetched with range ops,
+    /// `write_keys` are each conditionally overwritten if their current mod revision still
+    /// matches the one observed just before the txn is sent.
+    Txn {
+        read_keys: Vec<String>,
+        write_keys: Vec<(String, String)>,
+    },
 }
 
 impl YcsbInput {
@@ -115,6 +252,9 @@ impl YcsbInput {
             YcsbInput::ReadSingle { .. } => "read",
             YcsbInput::ReadAll { .. } => "read",
             YcsbInput::Scan { .. } => "scan",
+            YcsbInput::Watch { .. } => "watch",
+            YcsbInput::Verify { .. } => "verify",
+            YcsbInput::Txn { .. } => "txn",
         }
     }
 }
@@ -131,6 +271,9 @@ impl InputGenerator for YcsbInputGenerator {
             self.insert_weight,
             self.update_weight,
             self.rmw_weight,
+            self.watch_weight,
+            self.verify_weight,
+            self.txn_weight,
         ];
         let dist = WeightedAliasIndex::new(weights.to_vec()).unwrap();
         let weight_index = dist.sample(&mut self.operation_rng);
@@ -166,6 +309,37 @@ impl InputGenerator for YcsbInputGenerator {
                 field_key: Self::field_key(0),
                 field_value: random_string(self.field_value_length),
             },
+            // watch
+            5 => YcsbInput::Watch {
+                record_key: self.existing_record_key(),
+                field_key: Self::field_key(0),
+                field_value: random_string(self.field_value_length),
+            },
+            // verify
+            6 => YcsbInput::Verify {
+                record_key: self.existing_record_key(),
+                field_key: Self::field_key(0),
+            },
+            // txn
+            7 => {
+                let read_count = ((self.txn_size as f32 * self.txn_read_ratio).round() as u32)
+                    .min(self.txn_size);
+                let read_keys = (0..read_count)
+                    .map(|_| self.existing_record_key())
+                    .collect();
+                let write_keys = (0..self.txn_size - read_count)
+                    .map(|_| {
+                        (
+                            self.existing_record_key(),
+                            random_string(self.field_value_length),
+                        )
+                    })
+                    .collect();
+                YcsbInput::Txn {
+                    read_keys,
+                    write_keys,
+                }
+            }
             i => {
                 println!("got weight index {i}, but there was no input type to match");
                 return None;
@@ -185,12 +359,122 @@ fn random_string(len: usize) -> String {
     s
 }
 
+/// Shared state for the run's persistent watch population: `--watchers` long-lived streams set
+/// up once in [`WatchState::spawn`] and kept independent of the writer path, rather than a
+/// stream opened and torn down by every sampled `Watch` op.
+#[derive(Clone)]
+struct WatchState {
+    /// Put-ack timestamp and number of the `--watchers` streams still expected to observe it,
+    /// keyed by the raw etcd key a `Watch` op just wrote.
+    pending: Arc<tokio::sync::Mutex<HashMap<Vec<u8>, (Instant, u32)>>>,
+    /// Notification latencies (put-ack to delivery, in microseconds) the persistent streams have
+    /// observed since a `Watch` op last drained them.
+    latencies: Arc<tokio::sync::Mutex<Vec<u128>>>,
+    /// Watch events the persistent streams lost to compaction or an explicit cancellation,
+    /// rather than a clean delivery.
+    dropped_events: Arc<AtomicU64>,
+}
+
+impl WatchState {
+    /// Open `watchers` persistent watch streams over the whole keyspace once, so a `Watch` op
+    /// never pays stream setup/teardown cost and there's a real standing watcher population for
+    /// the run rather than one created and torn down per sampled op.
+    async fn spawn(watch_client: &WatchClient<Channel>, watchers: u32) -> Self {
+        let state = Self {
+            pending: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            latencies: Arc::new(tokio::sync::Mutex::new(Vec::new())),
+            dropped_events: Arc::new(AtomicU64::new(0)),
+        };
+        for _ in 0..watchers {
+            let create_request = WatchRequest {
+                request_union: Some(WatchRequestUnion::CreateRequest(WatchCreateRequest {
+                    key: vec![0],
+                    range_end: vec![0],
+                    ..Default::default()
+                })),
+            };
+            let mut watch_stream = watch_client
+                .clone()
+                .watch(futures_util::stream::once(async move { create_request }))
+                .await
+                .expect("failed to open persistent watch stream")
+                .into_inner();
+            match watch_stream.next().await {
+                Some(Ok(res)) if res.created => {}
+                other => panic!("expected a watch-created ack, got {other:?}"),
+            }
+            let state = state.clone();
+            tokio::spawn(async move {
+                while let Some(message) = watch_stream.next().await {
+                    let Ok(res) = message else { break };
+                    if res.canceled || res.compact_revision != 0 {
+                        state.dropped_events.fetch_add(1, Ordering::Relaxed);
+                        continue;
+                    }
+                    for event in res.events {
+                        let Some(kv) = event.kv else { continue };
+                        let mut pending = state.pending.lock().await;
+                        if let Some((put_start, remaining)) = pending.get_mut(&kv.key) {
+                            state
+                                .latencies
+                                .lock()
+                                .await
+                                .push(put_start.elapsed().as_micros());
+                            *remaining -= 1;
+                            if *remaining == 0 {
+                                pending.remove(&kv.key);
+                            }
+                        }
+                    }
+                }
+            });
+        }
+        state
+    }
+
+    /// Record that a `Watch` op's put just committed and each of the `watchers` streams is
+    /// expected to eventually observe it.
+    async fn expect_notification(&self, key: Vec<u8>, watchers: u32, put_start: Instant) {
+        if watchers > 0 {
+            self.pending.lock().await.insert(key, (put_start, watchers));
+        }
+    }
+
+    /// Drain whatever notification latencies have completed since this was last called,
+    /// returning their average, or `None` if none have completed yet. Because watchers are
+    /// decoupled from the writer path, a `Watch` op's reported latency reflects whatever the
+    /// persistent population has delivered by the time it runs, not necessarily just its own put.
+    async fn drain_latency_us(&self) -> Option<u128> {
+        let mut latencies = self.latencies.lock().await;
+        if latencies.is_empty() {
+            return None;
+        }
+        let count = latencies.len() as u128;
+        Some(latencies.drain(..).sum::<u128>() / count)
+    }
+
+    fn dropped_events(&self) -> u64 {
+        self.dropped_events.load(Ordering::Relaxed)
+    }
+}
+
 pub struct YcsbDispatcherGenerator {
     etcd_client: KvClient<Channel>,
+    receipt_client: ReceiptClient<Channel>,
+    watch_state: WatchState,
+    /// Number of persistent watch streams the `--watchers` population holds open.
+    watchers: u32,
+    /// Whether a `Txn` op gives up (rather than retrying) the first time its compare fails.
+    txn_abort_on_conflict: bool,
 }
 
 impl YcsbDispatcherGenerator {
-    pub async fn new(endpoint: String, common_dir: &Path) -> Self {
+    pub async fn new(
+        endpoint: String,
+        common_dir: &Path,
+        watchers: u32,
+        txn_abort_on_conflict: bool,
+    ) -> Self {
         let server_root_ca_cert =
             std::fs::read_to_string(common_dir.join("service_cert.pem")).unwrap();
         let server_root_ca_cert = Certificate::from_pem(server_root_ca_cert);
@@ -211,9 +495,16 @@ impl YcsbDispatcherGenerator {
             .await
             .unwrap();
 
-        let client = KvClient::new(channel);
+        let client = KvClient::new(channel.clone());
+        let watch_client = WatchClient::new(channel.clone());
+        let receipt_client = ReceiptClient::new(channel);
+        let watch_state = WatchState::spawn(&watch_client, watchers).await;
         Self {
             etcd_client: client,
+            receipt_client,
+            watch_state,
+            watchers,
+            txn_abort_on_conflict,
         }
     }
 }
@@ -224,17 +515,50 @@ impl DispatcherGenerator for YcsbDispatcherGenerator {
     fn generate(&mut self) -> Self::Dispatcher {
         YcsbDispatcher {
             etcd_client: self.etcd_client.clone(),
+            receipt_client: self.receipt_client.clone(),
+            watch_state: self.watch_state.clone(),
+            watchers: self.watchers,
+            txn_abort_on_conflict: self.txn_abort_on_conflict,
         }
     }
 }
 
 pub struct YcsbDispatcher {
     etcd_client: KvClient<Channel>,
+    receipt_client: ReceiptClient<Channel>,
+    watch_state: WatchState,
+    watchers: u32,
+    txn_abort_on_conflict: bool,
 }
 
+/// Bounded number of compare-and-swap attempts for a `ReadModifyWrite` before giving up.
+const RMW_MAX_RETRIES: u32 = 10;
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct YcsbOutput {
     operation: String,
+    /// Number of times a `ReadModifyWrite`'s or `Txn`'s txn compare failed and was retried.
+    #[serde(default)]
+    retries: u32,
+    /// For a `Watch` op, the average delay the persistent `--watchers` population has observed
+    /// between a put committing and its matching event being delivered, drained since the last
+    /// `Watch` op reported it, in microseconds. `None` if nothing has completed yet.
+    #[serde(default)]
+    watch_notify_latency_us: Option<u128>,
+    /// For a `Watch` op, the running total of watch events the persistent streams lost to
+    /// compaction or an explicit cancellation, so a watcher falling behind shows up here.
+    #[serde(default)]
+    watch_dropped_events: Option<u64>,
+    /// For a `Verify` op, the time taken to read the field, fetch its receipt and independently
+    /// fold its Merkle proof up to the signed root, in microseconds.
+    #[serde(default)]
+    verify_latency_us: Option<u128>,
+}
+
+impl YcsbOutput {
+    pub fn operation(&self) -> &str {
+        &self.operation
+    }
 }
 
 #[async_trait]
@@ -245,9 +569,37 @@ impl Dispatcher for YcsbDispatcher {
 
     async fn execute(&mut self, request: Self::Input) -> Result<Self::Output, String> {
         let operation = request.name().to_owned();
-        match request {
-            YcsbInput::Insert { record_key, fields } => {
-                for (field_key, field_value) in fields {
+        let mut retries = 0;
+        let mut watch_notify_latency_us = None;
+        let mut watch_dropped_events = None;
+        let mut verify_latency_us = None;
+        // Run the dispatch in its own async block so every early `return Err(...)` below only
+        // exits the op, not `execute` -- letting us tag the error with the operation that
+        // actually failed before it escapes, which `SummaryOutputSink` needs to bucket errors
+        // per operation type instead of under one undifferentiated total.
+        let result: Result<(), String> = async {
+            match request {
+                YcsbInput::Insert { record_key, fields } => {
+                    for (field_key, field_value) in fields {
+                        match self
+                            .etcd_client
+                            .put(PutRequest {
+                                key: format!("{record_key}/{field_key}").into(),
+                                value: field_value.into(),
+                                ..Default::default()
+                            })
+                            .await
+                        {
+                            Ok(_) => {}
+                            Err(err) => return Err(err.to_string()),
+                        }
+                    }
+                }
+                YcsbInput::Update {
+                    record_key,
+                    field_key,
+                    field_value,
+                } => {
                     match self
                         .etcd_client
                         .put(PutRequest {
@@ -261,109 +613,310 @@ impl Dispatcher for YcsbDispatcher {
                         Err(err) => return Err(err.to_string()),
                     }
                 }
-            }
-            YcsbInput::Update {
-                record_key,
-                field_key,
-                field_value,
-            } => {
-                match self
-                    .etcd_client
-                    .put(PutRequest {
-                        key: format!("{record_key}/{field_key}").into(),
-                        value: field_value.into(),
-                        ..Default::default()
-                    })
-                    .await
-                {
-                    Ok(_) => {}
-                    Err(err) => return Err(err.to_string()),
+                YcsbInput::ReadModifyWrite {
+                    record_key,
+                    field_key,
+                    field_value,
+                } => {
+                    let key = format!("{record_key}/{field_key}");
+                    loop {
+                        let mod_revision = match self
+                            .etcd_client
+                            .range(RangeRequest {
+                                key: key.clone().into(),
+                                range_end: vec![],
+                                serializable: true,
+                                ..Default::default()
+                            })
+                            .await
+                        {
+                            Ok(res) => res.into_inner().kvs.first().map_or(0, |kv| kv.mod_revision),
+                            Err(err) => return Err(err.to_string()),
+                        };
+
+                        let txn = self
+                            .etcd_client
+                            .txn(TxnRequest {
+                                compare: vec![Compare {
+                                    result: CompareResult::Equal as i32,
+                                    target: CompareTarget::Mod as i32,
+                                    key: key.clone().into_bytes(),
+                                    range_end: vec![],
+                                    target_union: Some(TargetUnion::ModRevision(mod_revision)),
+                                }],
+                                success: vec![RequestOp {
+                                    request: Some(RequestOpRequest::RequestPut(PutRequest {
+                                        key: key.clone().into_bytes(),
+                                        value: field_value.clone().into(),
+                                        ..Default::default()
+                                    })),
+                                }],
+                                failure: vec![],
+                            })
+                            .await;
+                        match txn {
+                            Ok(res) => {
+                                if res.into_inner().succeeded {
+                                    break;
+                                }
+                            }
+                            Err(err) => return Err(err.to_string()),
+                        }
+
+                        retries += 1;
+                        if retries >= RMW_MAX_RETRIES {
+                            return Err(format!(
+                                "rmw on {key} did not commit after {RMW_MAX_RETRIES} attempts"
+                            ));
+                        }
+                    }
                 }
-            }
-            YcsbInput::ReadModifyWrite {
-                record_key,
-                field_key,
-                field_value,
-            } => {
-                match self
-                    .etcd_client
-                    .range(RangeRequest {
-                        key: format!("{record_key}/{field_key}").into(),
-                        range_end: vec![],
-                        serializable: true,
-                        ..Default::default()
-                    })
-                    .await
-                {
-                    Ok(_) => {}
-                    Err(err) => return Err(err.to_string()),
+                YcsbInput::ReadSingle {
+                    record_key,
+                    field_key,
+                } => {
+                    match self
+                        .etcd_client
+                        .range(RangeRequest {
+                            key: format!("{record_key}/{field_key}").into(),
+                            range_end: vec![],
+                            serializable: true,
+                            ..Default::default()
+                        })
+                        .await
+                    {
+                        Ok(_) => {}
+                        Err(err) => return Err(err.to_string()),
+                    }
                 }
-
-                match self
-                    .etcd_client
-                    .put(PutRequest {
-                        key: format!("{record_key}/{field_key}").into(),
-                        value: field_value.into(),
-                        ..Default::default()
-                    })
-                    .await
-                {
-                    Ok(_) => {}
-                    Err(err) => return Err(err.to_string()),
+                YcsbInput::ReadAll { record_key } => {
+                    match self
+                        .etcd_client
+                        .range(RangeRequest {
+                            key: format!("{record_key}/").into(),
+                            range_end: format!("{record_key}0").into(),
+                            serializable: true,
+                            ..Default::default()
+                        })
+                        .await
+                    {
+                        Ok(_) => {}
+                        Err(err) => return Err(err.to_string()),
+                    }
                 }
-            }
-            YcsbInput::ReadSingle {
-                record_key,
-                field_key,
-            } => {
-                match self
-                    .etcd_client
-                    .range(RangeRequest {
-                        key: format!("{record_key}/{field_key}").into(),
-                        range_end: vec![],
-                        serializable: true,
-                        ..Default::default()
-                    })
-                    .await
-                {
-                    Ok(_) => {}
-                    Err(err) => return Err(err.to_string()),
+                YcsbInput::Scan { start_key, end_key } => {
+                    let key = start_key;
+                    let range_end = end_key;
+                    match self
+                        .etcd_client
+                        .range(RangeRequest {
+                            key: key.as_bytes().to_vec(),
+                            range_end: range_end.as_bytes().to_vec(),
+                            serializable: true,
+                            ..Default::default()
+                        })
+                        .await
+                    {
+                        Ok(_) => {}
+                        Err(err) => return Err(err.to_string()),
+                    };
                 }
-            }
-            YcsbInput::ReadAll { record_key } => {
-                match self
-                    .etcd_client
-                    .range(RangeRequest {
-                        key: format!("{record_key}/").into(),
-                        range_end: format!("{record_key}0").into(),
-                        serializable: true,
-                        ..Default::default()
-                    })
-                    .await
-                {
-                    Ok(_) => {}
-                    Err(err) => return Err(err.to_string()),
+                YcsbInput::Watch {
+                    record_key,
+                    field_key,
+                    field_value,
+                } => {
+                    let key = format!("{record_key}/{field_key}").into_bytes();
+
+                    // The persistent `--watchers` population (opened once in `WatchState::spawn`)
+                    // is already subscribed; just register that a notification is expected and
+                    // write, so this op pays no stream setup/teardown cost.
+                    let put_start = Instant::now();
+                    self.watch_state
+                        .expect_notification(key.clone(), self.watchers, put_start)
+                        .await;
+                    match self
+                        .etcd_client
+                        .put(PutRequest {
+                            key: key.clone(),
+                            value: field_value.into(),
+                            ..Default::default()
+                        })
+                        .await
+                    {
+                        Ok(_) => {}
+                        Err(err) => return Err(err.to_string()),
+                    }
+
+                    watch_notify_latency_us = self.watch_state.drain_latency_us().await;
+                    watch_dropped_events = Some(self.watch_state.dropped_events());
                 }
+                YcsbInput::Verify {
+                    record_key,
+                    field_key,
+                } => {
+                    let key = format!("{record_key}/{field_key}").into_bytes();
+                    let verify_start = std::time::Instant::now();
+
+                    let (value, mod_revision) = match self
+                        .etcd_client
+                        .range(RangeRequest {
+                            key: key.clone(),
+                            range_end: vec![],
+                            serializable: false,
+                            ..Default::default()
+                        })
+                        .await
+                    {
+                        Ok(res) => match res.into_inner().kvs.into_iter().next() {
+                            Some(kv) => (kv.value, kv.mod_revision),
+                            None => return Err(format!("no committed value found for {record_key}")),
+                        },
+                        Err(err) => return Err(err.to_string()),
+                    };
+
+                    let receipt = match self
+                        .receipt_client
+                        .get_receipt(GetReceiptRequest {
+                            revision: mod_revision,
+                        })
+                        .await
+                    {
+                        Ok(res) => match res.into_inner().receipt {
+                            Some(receipt) => receipt,
+                            None => {
+                                return Err(format!(
+                                    "server returned no receipt for {key:?}@{mod_revision}"
+                                ))
+                            }
+                        },
+                        Err(err) => return Err(err.to_string()),
+                    };
+
+                    // Recompute the leaf digest ourselves rather than trusting the one the server
+                    // sent alongside the proof.
+                    let mut leaf_input = Vec::with_capacity(key.len() + value.len() + 8);
+                    leaf_input.extend_from_slice(&key);
+                    leaf_input.extend_from_slice(&value);
+                    leaf_input.extend_from_slice(&mod_revision.to_be_bytes());
+                    let mut digest = Sha256::digest(&leaf_input).to_vec();
+
+                    // Fold the sibling digests up the tree in the order the server supplied them.
+                    for element in &receipt.proof {
+                        let mut hasher = Sha256::new();
+                        match &element.side {
+                            Some(proof_element::Side::Left(sibling)) => {
+                                hasher.update(sibling);
+                                hasher.update(&digest);
+                            }
+                            Some(proof_element::Side::Right(sibling)) => {
+                                hasher.update(&digest);
+                                hasher.update(sibling);
+                            }
+                            None => {
+                                return Err(
+                                    "proof element had neither a left nor right sibling".to_owned()
+                                )
+                            }
+                        }
+                        digest = hasher.finalize().to_vec();
+                    }
+
+                    if digest != receipt.root {
+                        return Err(format!(
+                            "receipt proof for {record_key}@{mod_revision} did not fold to the signed root"
+                        ));
+                    }
+
+                    verify_latency_us = Some(verify_start.elapsed().as_micros());
+                }
+                YcsbInput::Txn {
+                    read_keys,
+                    write_keys,
+                } => loop {
+                    let mut compare = Vec::with_capacity(write_keys.len());
+                    let mut success = Vec::with_capacity(read_keys.len() + write_keys.len());
+                    for read_key in &read_keys {
+                        success.push(RequestOp {
+                            request: Some(RequestOpRequest::RequestRange(RangeRequest {
+                                key: format!("{read_key}/{}", YcsbInputGenerator::field_key(0))
+                                    .into_bytes(),
+                                range_end: vec![],
+                                serializable: true,
+                                ..Default::default()
+                            })),
+                        });
+                    }
+                    for (record_key, field_value) in &write_keys {
+                        let key = format!("{record_key}/{}", YcsbInputGenerator::field_key(0));
+                        let mod_revision = match self
+                            .etcd_client
+                            .range(RangeRequest {
+                                key: key.clone().into(),
+                                range_end: vec![],
+                                serializable: true,
+                                ..Default::default()
+                            })
+                            .await
+                        {
+                            Ok(res) => res.into_inner().kvs.first().map_or(0, |kv| kv.mod_revision),
+                            Err(err) => return Err(err.to_string()),
+                        };
+                        compare.push(Compare {
+                            result: CompareResult::Equal as i32,
+                            target: CompareTarget::Mod as i32,
+                            key: key.clone().into_bytes(),
+                            range_end: vec![],
+                            target_union: Some(TargetUnion::ModRevision(mod_revision)),
+                        });
+                        success.push(RequestOp {
+                            request: Some(RequestOpRequest::RequestPut(PutRequest {
+                                key: key.into_bytes(),
+                                value: field_value.clone().into(),
+                                ..Default::default()
+                            })),
+                        });
+                    }
+
+                    let txn = self
+                        .etcd_client
+                        .txn(TxnRequest {
+                            compare,
+                            success,
+                            failure: vec![],
+                        })
+                        .await;
+                    match txn {
+                        Ok(res) => {
+                            if res.into_inner().succeeded {
+                                break;
+                            }
+                        }
+                        Err(err) => return Err(err.to_string()),
+                    }
+
+                    if self.txn_abort_on_conflict {
+                        return Err("txn aborted: a write's compare no longer held".to_owned());
+                    }
+                    retries += 1;
+                    if retries >= RMW_MAX_RETRIES {
+                        return Err(format!(
+                            "txn did not commit after {RMW_MAX_RETRIES} attempts"
+                        ));
+                    }
+                },
             }
-            YcsbInput::Scan { start_key, end_key } => {
-                let key = start_key;
-                let range_end = end_key;
-                match self
-                    .etcd_client
-                    .range(RangeRequest {
-                        key: key.as_bytes().to_vec(),
-                        range_end: range_end.as_bytes().to_vec(),
-                        serializable: true,
-                        ..Default::default()
-                    })
-                    .await
-                {
-                    Ok(_) => {}
-                    Err(err) => return Err(err.to_string()),
-                };
-            }
+            Ok(())
         }
-        Ok(YcsbOutput { operation })
+        .await;
+        result.map_err(|err| format!("{operation}: {err}"))?;
+        Ok(YcsbOutput {
+            operation,
+            retries,
+            watch_notify_latency_us,
+            watch_dropped_events,
+            verify_latency_us,
+        })
     }
 }
 
@@ -388,6 +941,26 @@ pub struct Args {
     pub update_weight: u32,
     #[clap(long, default_value = "0")]
     pub rmw_weight: u32,
+    #[clap(long, default_value = "0")]
+    pub watch_weight: u32,
+    /// Number of persistent watch streams opened once over the whole keyspace at startup,
+    /// independent of the writer path, each expected to observe every `watch` op's put.
+    #[clap(long, default_value = "1")]
+    pub watchers: u32,
+    #[clap(long, default_value = "0")]
+    pub verify_weight: u32,
+    #[clap(long, default_value = "0")]
+    pub txn_weight: u32,
+    /// Number of keys bundled into each `txn` op.
+    #[clap(long, default_value = "2")]
+    pub txn_size: u32,
+    /// Fraction (`0.0..=1.0`) of a `txn` op's keys that are pure reads rather than
+    /// compare-and-write.
+    #[clap(long, default_value = "0.5")]
+    pub txn_read_ratio: f32,
+    /// Whether a `txn` op gives up (rather than retrying) the first time its compare fails.
+    #[clap(long, default_value = "false")]
+    pub txn_abort_on_conflict: bool,
     #[clap(long, default_value = "1")]
     pub fields_per_record: u32,
     #[clap(long, default_value = "1")]
@@ -398,4 +971,6 @@ pub struct Args {
     pub max_scan_length: u32,
     #[clap(long, default_value = "uniform")]
     pub request_distribution: RequestDistribution,
+    #[clap(long, default_value = "0.99")]
+    pub zipfian_theta: f64,
 }
@@ -5,6 +5,8 @@ use tracing::metadata::LevelFilter;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 
+mod metrics;
+mod profiler;
 mod stores;
 mod ycsb;
 
@@ -21,6 +23,21 @@ struct Args {
 
     #[clap(long)]
     analyse: bool,
+
+    /// Path to a TOML or JSON file of custom YCSB workload definitions, run in addition to the
+    /// textbook A-F workloads.
+    #[clap(long)]
+    workloads_file: Option<PathBuf>,
+
+    /// If set, fail fast unless the store reports a version satisfying this semver range during
+    /// its readiness handshake (e.g. `>=3.5.0, <3.6.0`).
+    #[clap(long)]
+    require_version: Option<semver::VersionReq>,
+
+    /// Capture a CPU flamegraph of the store process during the measured load-generation
+    /// window of each configuration.
+    #[clap(long, value_enum, default_value = "none")]
+    profile: profiler::ProfileMode,
 }
 
 #[tokio::main]
@@ -39,16 +56,38 @@ async fn main() {
 
     let mut experiment = ycsb::YcsbExperiment {
         root_dir: args.root_dir.clone(),
+        distributed: false,
+        workloads_file: args.workloads_file.clone(),
+        require_version: args.require_version.clone(),
+        profile: args.profile,
     };
     if args.run {
-        exp::run(
-            &mut experiment,
-            &exp::RunConfig {
-                results_dir: args.results_dir.clone(),
-            },
-        )
-        .await
-        .unwrap();
+        // `exp::run` owns the cluster subprocess for the run's duration; if Ctrl-C fires we
+        // abort this task rather than `exp::run` itself so its `ManagedChild` is dropped and its
+        // `Drop` guard reaps the cluster instead of leaving it orphaned.
+        let results_dir = args.results_dir.clone();
+        let mut handle = tokio::spawn(async move {
+            exp::run(
+                &mut experiment,
+                &exp::RunConfig {
+                    results_dir: results_dir.clone(),
+                },
+            )
+            .await
+            .unwrap();
+            experiment
+        });
+        tokio::select! {
+            result = &mut handle => {
+                experiment = result.expect("run task panicked");
+            }
+            _ = tokio::signal::ctrl_c() => {
+                info!("received Ctrl-C, tearing down cluster");
+                handle.abort();
+                let _ = handle.await;
+                return;
+            }
+        }
     }
     if args.analyse {
         exp::analyse(
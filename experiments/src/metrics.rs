@@ -0,0 +1,167 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::debug;
+use tracing::warn;
+
+use crate::stores::StoreConfig;
+
+/// A single `(wall_clock_ms, member_id, metric_name, value)` sample scraped from a node's
+/// Prometheus-style `/metrics` endpoint.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MetricSample {
+    pub wall_clock_ms: u128,
+    pub member_id: u64,
+    pub metric_name: String,
+    pub value: f64,
+}
+
+/// Background scrape tasks for a single configuration, one per node.
+///
+/// Created with [`MetricsScraper::start`] once the cluster is ready, and torn down with
+/// [`MetricsScraper::stop`] before the store is shut down.
+pub struct MetricsScraper {
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl MetricsScraper {
+    /// Spawn one scraping task per node, polling its `/metrics` endpoint every `interval` and
+    /// appending samples to `configuration_dir/metrics-{member_id}.csv`. `store_config`
+    /// determines how a node address maps to its metrics URL, since that differs by store (see
+    /// [`node_to_metrics_url`]).
+    pub fn start(
+        store_config: &StoreConfig,
+        nodes: &[String],
+        configuration_dir: &Path,
+        interval: Duration,
+    ) -> Self {
+        let start = Instant::now();
+        let handles = nodes
+            .iter()
+            .enumerate()
+            .map(|(member_id, node)| {
+                let node = node.clone();
+                let store_config = store_config.clone();
+                let out_path = configuration_dir.join(format!("metrics-{member_id}.csv"));
+                let member_id = member_id as u64;
+                tokio::spawn(scrape_loop(
+                    member_id,
+                    node,
+                    store_config,
+                    out_path,
+                    interval,
+                    start,
+                ))
+            })
+            .collect();
+        Self { handles }
+    }
+
+    /// Stop all scrape tasks.
+    pub async fn stop(self) {
+        for handle in self.handles {
+            handle.abort();
+            let _ = handle.await;
+        }
+    }
+}
+
+async fn scrape_loop(
+    member_id: u64,
+    node: String,
+    store_config: StoreConfig,
+    out_path: PathBuf,
+    interval: Duration,
+    start: Instant,
+) {
+    let address = node_to_metrics_url(&node, &store_config);
+    let client = reqwest::Client::new();
+    let mut writer = match csv::Writer::from_path(&out_path) {
+        Ok(writer) => writer,
+        Err(err) => {
+            warn!(?err, ?out_path, "failed to create metrics output file");
+            return;
+        }
+    };
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let wall_clock_ms = start.elapsed().as_millis();
+        match client.get(&address).send().await {
+            Ok(res) => match res.text().await {
+                Ok(body) => {
+                    for (metric_name, value) in parse_prometheus_text(&body) {
+                        let sample = MetricSample {
+                            wall_clock_ms,
+                            member_id,
+                            metric_name,
+                            value,
+                        };
+                        if let Err(err) = writer.serialize(&sample) {
+                            warn!(?err, "failed to write metric sample");
+                        }
+                    }
+                    if let Err(err) = writer.flush() {
+                        warn!(?err, "failed to flush metrics writer");
+                    }
+                }
+                Err(err) => debug!(?err, "failed to read metrics response body"),
+            },
+            Err(err) => debug!(?err, ?address, "failed to scrape metrics endpoint"),
+        }
+    }
+}
+
+/// Turn a node address of the form `scheme://host:port` into its metrics URL. etcd exposes
+/// Prometheus metrics on its secondary HTTP listener at `client_port + 2` -- the same surface
+/// `EtcdStore::is_ready`/`fetch_version` scrape for `/health` and `/version` -- rather than the
+/// client gRPC port, so the offset must be applied there but not for Lskv.
+fn node_to_metrics_url(node: &str, store_config: &StoreConfig) -> String {
+    let address = node.split("://").nth(1).unwrap_or(node);
+    let address = match store_config {
+        StoreConfig::Etcd(_) => offset_port(address, 2),
+        StoreConfig::Lskv(_) => address.to_owned(),
+    };
+    format!("http://{address}/metrics")
+}
+
+/// Adds `offset` to the port of a `host:port` address, leaving it unchanged if it can't be
+/// parsed as one.
+fn offset_port(address: &str, offset: u16) -> String {
+    match address.rsplit_once(':') {
+        Some((host, port)) => match port.parse::<u16>() {
+            Ok(port) => format!("{host}:{}", port + offset),
+            Err(_) => address.to_owned(),
+        },
+        None => address.to_owned(),
+    }
+}
+
+/// Parse a Prometheus text-exposition-format payload into `(name, value)` pairs, skipping
+/// comment/TYPE/HELP lines and dropping any `{labels}` suffix on the metric name.
+fn parse_prometheus_text(body: &str) -> Vec<(String, f64)> {
+    let mut samples = Vec::new();
+    for line in body.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let Some(name_and_labels) = parts.next() else {
+            continue;
+        };
+        let Some(value) = parts.next().and_then(|v| v.parse::<f64>().ok()) else {
+            continue;
+        };
+        let metric_name = name_and_labels
+            .split('{')
+            .next()
+            .unwrap_or(name_and_labels)
+            .to_owned();
+        samples.push((metric_name, value));
+    }
+    samples
+}
@@ -0,0 +1,102 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Child;
+use std::process::Command;
+
+use inferno::collapse::Collapse;
+use tracing::warn;
+
+/// Whether to capture a CPU flamegraph of the store process during the measured load-generation
+/// window, via `--profile`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ProfileMode {
+    #[default]
+    None,
+    /// Sample the store process with `perf record` and render `flamegraph.svg` in the
+    /// configuration directory once the run completes.
+    Flamegraph,
+}
+
+/// Samples a store process with `perf record` for as long as it's alive, so the recorded stacks
+/// line up with exactly the window between [`start`](Self::start) (called once the load phase has
+/// populated the store) and the measured run finishing ([`stop_and_render`](Self::stop_and_render)).
+pub struct FlamegraphProfiler {
+    child: Child,
+    perf_data: PathBuf,
+}
+
+impl FlamegraphProfiler {
+    /// Starts sampling `pids` (the real store node server process(es), not the
+    /// `benchmark/*_cluster.py` launcher that spawned them), writing raw samples to
+    /// `configuration_dir/perf.data`. Returns `None` (logging a warning) rather than failing the
+    /// run if `perf` isn't available or no node PIDs were discovered.
+    pub fn start(configuration_dir: &Path, pids: &[u32]) -> Option<Self> {
+        if pids.is_empty() {
+            warn!("no node pids to profile, profiling disabled for this run");
+            return None;
+        }
+        let pid_list = pids
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        let perf_data = configuration_dir.join("perf.data");
+        match Command::new("perf")
+            .args(["record", "-g", "-F", "997", "-o"])
+            .arg(&perf_data)
+            .args(["--pid", &pid_list])
+            .spawn()
+        {
+            Ok(child) => Some(Self { child, perf_data }),
+            Err(err) => {
+                warn!(
+                    ?err,
+                    "failed to start perf record, profiling disabled for this run"
+                );
+                None
+            }
+        }
+    }
+
+    /// Stops sampling and folds the captured stacks into `configuration_dir/flamegraph.svg`.
+    pub async fn stop_and_render(mut self, configuration_dir: &Path) {
+        let _ = nix::sys::signal::kill(
+            nix::unistd::Pid::from_raw(self.child.id() as i32),
+            nix::sys::signal::Signal::SIGINT,
+        );
+        let _ = self.child.wait();
+
+        let perf_script = Command::new("perf")
+            .args(["script", "-i"])
+            .arg(&self.perf_data)
+            .output();
+        let Ok(perf_script) = perf_script else {
+            warn!("failed to run `perf script`, skipping flamegraph rendering");
+            return;
+        };
+
+        let mut folded = Vec::new();
+        if let Err(err) = inferno::collapse::perf::Folder::default()
+            .collapse(&perf_script.stdout[..], &mut folded)
+        {
+            warn!(
+                ?err,
+                "failed to fold perf samples, skipping flamegraph rendering"
+            );
+            return;
+        }
+
+        let svg_path = configuration_dir.join("flamegraph.svg");
+        let svg = match std::fs::File::create(&svg_path) {
+            Ok(f) => f,
+            Err(err) => {
+                warn!(?err, ?svg_path, "failed to create flamegraph output file");
+                return;
+            }
+        };
+        let mut options = inferno::flamegraph::Options::default();
+        if let Err(err) = inferno::flamegraph::from_reader(&mut options, &folded[..], svg) {
+            warn!(?err, "failed to render flamegraph");
+        }
+    }
+}
@@ -1,3 +1,11 @@
+use std::path::Path;
+use std::process::Child;
+use std::time::Duration;
+
+use nix::sys::signal::Signal;
+use nix::unistd::Pid;
+use tracing::warn;
+
 pub mod etcd;
 pub mod lskv;
 
@@ -7,3 +15,115 @@ pub enum StoreConfig {
     Lskv(lskv::Config),
     Etcd(etcd::Config),
 }
+
+/// How long [`ManagedChild::shutdown`] waits for a graceful exit before escalating to `SIGKILL`.
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Spawns the `benchmark/*_cluster.py` process for a store, and manages the rest of its
+/// lifecycle: a clean [`shutdown`](ManagedChild::shutdown) of the returned child, plus a [`wait_for_ready`](Store::wait_for_ready)
+/// handshake done once the cluster is up. `Ready` carries whatever the handshake discovers about
+/// the running cluster (e.g. [`lskv::ServerVersion`]) back to the caller.
+#[async_trait::async_trait]
+pub trait Store {
+    type Ready;
+
+    fn run(&self, root_dir: &Path) -> ManagedChild;
+
+    async fn wait_for_ready(&self) -> Self::Ready;
+
+    /// Returns the PID(s) of the actual store node server process(es) that the `run`'s
+    /// `ManagedChild` launcher spawns underneath it, as opposed to [`ManagedChild::id`] (the
+    /// launcher's own PID) -- for attaching a profiler or signal to the process that's actually
+    /// doing the work being measured, rather than the `benchmark/*_cluster.py` wrapper.
+    fn node_pids(&self) -> Vec<u32>;
+}
+
+/// Reads the real store node server PID(s) that a `benchmark/*_cluster.py` launcher writes to
+/// `<workspace>/node.pids` (one per line) once it has spawned them. Returns an empty `Vec`
+/// (logging a warning) if the file can't be read, rather than failing the run.
+pub fn read_node_pids(workspace: &Path) -> Vec<u32> {
+    let path = workspace.join("node.pids");
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => contents
+            .lines()
+            .filter_map(|line| line.trim().parse::<u32>().ok())
+            .collect(),
+        Err(err) => {
+            warn!(?err, ?path, "failed to read node pids");
+            Vec::new()
+        }
+    }
+}
+
+/// A cluster subprocess spawned as the leader of its own process group, so a single `shutdown`
+/// or `Drop` reaps it and every node it launched underneath, rather than leaving them as orphans
+/// when a benchmark run completes or panics.
+pub struct ManagedChild {
+    child: Option<Child>,
+}
+
+impl ManagedChild {
+    pub fn new(child: Child) -> Self {
+        Self { child: Some(child) }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.child.as_ref().expect("child already shut down").id()
+    }
+
+    /// Sends `signal` to the child's process group (not just the launcher itself) and blocks
+    /// until it exits. Used to simulate a node crash, as opposed to the graceful [`shutdown`] --
+    /// the launcher spawns the real store node process(es) underneath it in the same process
+    /// group, so signalling only the launcher's own PID would leave them running. Consumes
+    /// `self`, like `shutdown`, so the now-dead handle can't later trip `Drop`'s forceful-reap
+    /// warning and re-signal an already-reaped process group.
+    pub fn kill_and_wait(mut self, signal: Signal) {
+        let Some(mut child) = self.child.take() else {
+            return;
+        };
+        let pgid = Pid::from_raw(child.id() as i32);
+        let _ = nix::sys::signal::killpg(pgid, signal);
+        let _ = child.wait();
+    }
+
+    /// Sends `SIGTERM` to the child's process group, waits up to `timeout` for it to exit, then
+    /// escalates to `SIGKILL`. Returns whether the launcher process exited on its own within
+    /// `timeout`.
+    pub async fn shutdown(mut self, timeout: Duration) -> bool {
+        let Some(mut child) = self.child.take() else {
+            return true;
+        };
+        let pgid = Pid::from_raw(child.id() as i32);
+        let _ = nix::sys::signal::killpg(pgid, Signal::SIGTERM);
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            if matches!(child.try_wait(), Ok(Some(_))) {
+                return true;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        warn!(
+            ?timeout,
+            "cluster did not exit in time, escalating to SIGKILL"
+        );
+        let _ = nix::sys::signal::killpg(pgid, Signal::SIGKILL);
+        let _ = child.wait();
+        false
+    }
+}
+
+impl Drop for ManagedChild {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            warn!("ManagedChild dropped without an explicit shutdown, reaping forcefully");
+            let pgid = Pid::from_raw(child.id() as i32);
+            let _ = nix::sys::signal::killpg(pgid, Signal::SIGKILL);
+            let _ = child.wait();
+        }
+    }
+}
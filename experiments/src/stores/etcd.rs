@@ -1,7 +1,7 @@
 use std::fs::File;
+use std::os::unix::process::CommandExt;
 use std::path::Path;
 use std::path::PathBuf;
-use std::process::Child;
 use std::process::Command;
 use std::time::Duration;
 
@@ -11,19 +11,36 @@ use serde::Serialize;
 use tracing::debug;
 use tracing::info;
 
+use crate::stores::ManagedChild;
+use crate::stores::Store;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {}
 
+/// The etcd server/cluster version a run was measured against, recorded as part of the
+/// readiness handshake so results can be tagged with the exact version they came from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EtcdVersion {
+    pub etcdserver: String,
+    pub etcdcluster: String,
+}
+
 pub struct EtcdStore {
     pub config: Config,
     pub nodes: Vec<String>,
     pub configuration_dir: PathBuf,
     pub workspace: PathBuf,
     pub tmpfs: bool,
+    /// If set, the readiness handshake fails fast unless the detected `etcdserver` version
+    /// satisfies this range, via `--require-version`.
+    pub require_version: Option<semver::VersionReq>,
 }
 
-impl EtcdStore {
-    pub fn run(&self, root_dir: &Path) -> Child {
+#[async_trait::async_trait]
+impl Store for EtcdStore {
+    type Ready = EtcdVersion;
+
+    fn run(&self, root_dir: &Path) -> ManagedChild {
         let mut args = vec![
             "benchmark/etcd_cluster.py".to_owned(),
             "--workspace".to_owned(),
@@ -38,16 +55,20 @@ impl EtcdStore {
         }
         let out_file = File::create(self.configuration_dir.join("runner.out")).unwrap();
         let err_file = File::create(self.configuration_dir.join("runner.err")).unwrap();
-        Command::new("python3")
+        let child = Command::new("python3")
             .args(args)
             .stdout(out_file)
             .stderr(err_file)
             .current_dir(root_dir)
+            // Make the cluster script the leader of its own process group, so a single
+            // `ManagedChild::shutdown` can signal it and every etcd node it launches.
+            .process_group(0)
             .spawn()
-            .unwrap()
+            .unwrap();
+        ManagedChild::new(child)
     }
 
-    pub async fn wait_for_ready(&self) {
+    async fn wait_for_ready(&self) -> EtcdVersion {
         debug!("waiting for ready");
         for _ in 0..100 {
             let mut all = true;
@@ -62,6 +83,56 @@ impl EtcdStore {
             }
             tokio::time::sleep(Duration::from_millis(1000)).await;
         }
+        self.check_version().await
+    }
+
+    fn node_pids(&self) -> Vec<u32> {
+        crate::stores::read_node_pids(&self.workspace)
+    }
+}
+
+impl EtcdStore {
+    /// Fetch the detected node's `/version` and, if `--require-version` was given, assert it
+    /// satisfies the requested range, failing fast with a clear error rather than letting an
+    /// incompatible run produce results that silently aren't comparable to others.
+    async fn check_version(&self) -> EtcdVersion {
+        let node = self.nodes.first().expect("no nodes configured");
+        let version = self
+            .fetch_version(node)
+            .await
+            .expect("failed to fetch node version during readiness handshake");
+        if let Some(constraint) = &self.require_version {
+            let parsed = semver::Version::parse(&version.etcdserver).unwrap_or_else(|err| {
+                panic!(
+                    "node {node} reports unparseable etcd version {}: {err}",
+                    version.etcdserver
+                )
+            });
+            assert!(
+                constraint.matches(&parsed),
+                "node {node} reports etcd version {}, which does not satisfy the required \
+                 version {constraint}",
+                version.etcdserver
+            );
+        }
+        info!(?version, "Confirmed node version is compatible");
+        version
+    }
+
+    async fn fetch_version(&self, node: &str) -> Option<EtcdVersion> {
+        debug!("Fetching version from node {node}");
+        let address: Url = node.parse().ok()?;
+        let address = format!("{}:{}", address.host()?, address.port()? + 2);
+        let client = reqwest::Client::builder().build().ok()?;
+        let res = client
+            .get(format!("http://{}/version", address))
+            .send()
+            .await
+            .ok()?;
+        if !res.status().is_success() {
+            return None;
+        }
+        res.json::<EtcdVersion>().await.ok()
     }
 
     async fn is_ready(&self, node: &str) -> bool {
@@ -1,9 +1,9 @@
 use std::fmt::Display;
 use std::fs::File;
 use std::io::Read;
+use std::os::unix::process::CommandExt;
 use std::path::Path;
 use std::path::PathBuf;
-use std::process::Child;
 use std::process::Command;
 use std::time::Duration;
 
@@ -13,11 +13,16 @@ use serde::Serialize;
 use tracing::debug;
 use tracing::info;
 
+use crate::stores::ManagedChild;
+use crate::stores::Store;
+
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Enclave {
     Virtual,
     SGX,
+    /// AMD SEV-SNP, CCF's confidential-VM-based attestation backend.
+    SNP,
 }
 
 impl Display for Enclave {
@@ -28,11 +33,24 @@ impl Display for Enclave {
             match self {
                 Self::Virtual => "virtual",
                 Self::SGX => "sgx",
+                Self::SNP => "snp",
             }
         )
     }
 }
 
+/// The CCF release line this benchmark's generated etcd-API protos (`protos::etcdserverpb`,
+/// `protos::lskvserverpb`) were built against. Bump the prefix whenever the vendored protos are
+/// regenerated from a newer CCF release.
+const SUPPORTED_CCF_VERSION_PREFIX: &str = "4.";
+
+/// The server build a run was measured against, recorded as part of the readiness handshake so
+/// results can be tagged with the exact version they came from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerVersion {
+    pub ccf_version: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub enclave: Enclave,
@@ -50,10 +68,16 @@ pub struct LskvStore {
     pub workspace: PathBuf,
     pub http_version: u8,
     pub tmpfs: bool,
+    /// If set, the readiness handshake fails fast unless the detected CCF version satisfies this
+    /// range, via `--require-version`.
+    pub require_version: Option<semver::VersionReq>,
 }
 
-impl LskvStore {
-    pub fn run(&self, root_dir: &Path) -> Child {
+#[async_trait::async_trait]
+impl Store for LskvStore {
+    type Ready = ServerVersion;
+
+    fn run(&self, root_dir: &Path) -> ManagedChild {
         let mut args = vec![
             "benchmark/lskv_cluster.py".to_owned(),
             "--enclave".to_owned(),
@@ -82,16 +106,20 @@ impl LskvStore {
         }
         let out_file = File::create(self.configuration_dir.join("runner.out")).unwrap();
         let err_file = File::create(self.configuration_dir.join("runner.err")).unwrap();
-        Command::new("python3")
+        let child = Command::new("python3")
             .args(args)
             .stdout(out_file)
             .stderr(err_file)
             .current_dir(root_dir)
+            // Make the cluster script the leader of its own process group, so a single
+            // `ManagedChild::shutdown` can signal it and every node it launches.
+            .process_group(0)
             .spawn()
-            .unwrap()
+            .unwrap();
+        ManagedChild::new(child)
     }
 
-    pub async fn wait_for_ready(&self) {
+    async fn wait_for_ready(&self) -> ServerVersion {
         debug!("waiting for ready");
         for _ in 0..100 {
             let mut all = true;
@@ -106,6 +134,81 @@ impl LskvStore {
             }
             tokio::time::sleep(Duration::from_millis(1000)).await;
         }
+        self.check_version().await
+    }
+
+    fn node_pids(&self) -> Vec<u32> {
+        crate::stores::read_node_pids(&self.workspace)
+    }
+}
+
+impl LskvStore {
+    /// Fetch the detected node's version/commit info and assert it is compatible with the
+    /// etcd-API protos this benchmark was built against, failing fast with a clear error rather
+    /// than a cryptic tonic error mid-run. If `--require-version` was given, also assert the
+    /// version satisfies that range.
+    async fn check_version(&self) -> ServerVersion {
+        let node = self.nodes.first().expect("no nodes configured");
+        let version = self
+            .fetch_version(node)
+            .await
+            .expect("failed to fetch node version during readiness handshake");
+        assert!(
+            version
+                .ccf_version
+                .starts_with(SUPPORTED_CCF_VERSION_PREFIX),
+            "node {node} reports CCF version {}, incompatible with the etcd-API protos this \
+             benchmark was built against (expected {SUPPORTED_CCF_VERSION_PREFIX}x)",
+            version.ccf_version
+        );
+        if let Some(constraint) = &self.require_version {
+            let parsed = semver::Version::parse(&version.ccf_version).unwrap_or_else(|err| {
+                panic!(
+                    "node {node} reports unparseable CCF version {}: {err}",
+                    version.ccf_version
+                )
+            });
+            assert!(
+                constraint.matches(&parsed),
+                "node {node} reports CCF version {}, which does not satisfy the required \
+                 version {constraint}",
+                version.ccf_version
+            );
+        }
+        info!(?version, "Confirmed node version is compatible");
+        version
+    }
+
+    async fn fetch_version(&self, node: &str) -> Option<ServerVersion> {
+        debug!("Fetching version from node {node}");
+        let ca = self.workspace.join("common").join("service_cert.pem");
+        let mut ca_contents = Vec::new();
+        File::open(ca)
+            .and_then(|mut f| f.read_to_end(&mut ca_contents))
+            .ok()?;
+        let certificate = reqwest::tls::Certificate::from_pem(&ca_contents).ok()?;
+        let address = node
+            .split("://")
+            .skip(1)
+            .map(|s| s.to_owned())
+            .collect::<Vec<String>>()
+            .join("");
+        let client = reqwest::Client::builder()
+            // CCF doesn't currently support ALPN
+            // https://github.com/microsoft/CCF/issues/4814
+            .http2_prior_knowledge()
+            .add_root_certificate(certificate)
+            .build()
+            .ok()?;
+        let res = client
+            .get(format!("https://{}/node/version", address))
+            .send()
+            .await
+            .ok()?;
+        if !res.status().is_success() {
+            return None;
+        }
+        res.json::<ServerVersion>().await.ok()
     }
 
     async fn is_ready(&self, node: &str) -> bool {
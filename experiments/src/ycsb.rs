@@ -1,7 +1,12 @@
+use crate::metrics::MetricsScraper;
+use crate::profiler::FlamegraphProfiler;
+use crate::profiler::ProfileMode;
 use crate::stores::etcd::EtcdStore;
 use crate::stores::lskv::Enclave;
 use crate::stores::lskv::LskvStore;
+use crate::stores::Store;
 use crate::stores::StoreConfig;
+use crate::stores::SHUTDOWN_TIMEOUT;
 use exp::Environment;
 use exp::Experiment;
 use futures_util::StreamExt;
@@ -11,12 +16,25 @@ use std::fs::File;
 use std::io::Read;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
 use tracing::debug;
 use tracing::info;
 
+/// How often each node's `/metrics` endpoint is scraped for the duration of a configuration.
+const METRICS_SCRAPE_INTERVAL: Duration = Duration::from_millis(1_000);
+
 pub struct YcsbExperiment {
     pub root_dir: PathBuf,
     pub distributed: bool,
+    /// Custom workload specs loaded from `--workloads-file`, run in addition to the textbook
+    /// A-F workloads if present.
+    pub workloads_file: Option<PathBuf>,
+    /// If set, each store's readiness handshake fails fast unless its reported version satisfies
+    /// this range, from `--require-version`.
+    pub require_version: Option<semver::VersionReq>,
+    /// Whether to capture a CPU flamegraph of the store process during the measured
+    /// load-generation window, from `--profile`.
+    pub profile: ProfileMode,
 }
 
 #[async_trait::async_trait]
@@ -48,8 +66,17 @@ impl Experiment for YcsbExperiment {
             ledger_chunk_bytes: ledger_chunk_bytes.to_owned(),
             snapshot_tx_interval,
         });
+        let lskv_snp_config = StoreConfig::Lskv(crate::stores::lskv::Config {
+            enclave: Enclave::SNP,
+            worker_threads,
+            sig_tx_interval,
+            sig_ms_interval,
+            ledger_chunk_bytes: ledger_chunk_bytes.to_owned(),
+            snapshot_tx_interval,
+        });
         store_configs.push(lskv_virtual_config.clone());
         store_configs.push(lskv_sgx_config.clone());
+        store_configs.push(lskv_snp_config.clone());
         let rate = 10_000;
         for nodes in [3] {
             for workload in [
@@ -70,6 +97,27 @@ impl Experiment for YcsbExperiment {
                             nodes,
                             tmpfs,
                             max_clients: Some(100),
+                            measure_recovery: false,
+                        };
+                        configs.push(config);
+                    }
+                }
+            }
+        }
+        if let Some(workloads_file) = &self.workloads_file {
+            let workloads = load_workloads_file(workloads_file);
+            for workload in workloads {
+                for store_config in &store_configs {
+                    for tmpfs in [false, true] {
+                        let config = Config {
+                            store_config: store_config.clone(),
+                            rate,
+                            total: rate * 10,
+                            workload: YcsbWorkload::Custom(workload.clone()),
+                            nodes: 3,
+                            tmpfs,
+                            max_clients: Some(100),
+                            measure_recovery: false,
                         };
                         configs.push(config);
                     }
@@ -77,7 +125,11 @@ impl Experiment for YcsbExperiment {
             }
         }
         for nodes in [1, 3, 5, 7] {
-            for store_config in [lskv_sgx_config.clone(), lskv_virtual_config.clone()] {
+            for store_config in [
+                lskv_sgx_config.clone(),
+                lskv_snp_config.clone(),
+                lskv_virtual_config.clone(),
+            ] {
                 let config = Config {
                     store_config: store_config.clone(),
                     rate,
@@ -86,12 +138,17 @@ impl Experiment for YcsbExperiment {
                     nodes,
                     tmpfs: false,
                     max_clients: Some(100),
+                    measure_recovery: false,
                 };
                 configs.push(config);
             }
         }
         for sig_ms_interval in [100, 1000] {
-            for store_config in &[lskv_virtual_config.clone(), lskv_sgx_config.clone()] {
+            for store_config in &[
+                lskv_virtual_config.clone(),
+                lskv_sgx_config.clone(),
+                lskv_snp_config.clone(),
+            ] {
                 let store_config = match store_config.clone() {
                     StoreConfig::Lskv(mut l) => {
                         l.sig_ms_interval = sig_ms_interval;
@@ -107,12 +164,17 @@ impl Experiment for YcsbExperiment {
                     nodes: 3,
                     tmpfs: false,
                     max_clients: Some(100),
+                    measure_recovery: false,
                 };
                 configs.push(config);
             }
         }
         for worker_threads in [0, 1, 2, 4] {
-            for store_config in &[lskv_virtual_config.clone(), lskv_sgx_config.clone()] {
+            for store_config in &[
+                lskv_virtual_config.clone(),
+                lskv_sgx_config.clone(),
+                lskv_snp_config.clone(),
+            ] {
                 let store_config = match store_config.clone() {
                     StoreConfig::Lskv(mut l) => {
                         l.worker_threads = worker_threads;
@@ -128,6 +190,30 @@ impl Experiment for YcsbExperiment {
                     nodes: 3,
                     tmpfs: false,
                     max_clients: Some(100),
+                    measure_recovery: false,
+                };
+                configs.push(config);
+            }
+        }
+        for snapshot_tx_interval in [10, 100, 1_000] {
+            for ledger_chunk_bytes in ["1MB", "5MB", "20MB"] {
+                let store_config = match lskv_virtual_config.clone() {
+                    StoreConfig::Lskv(mut l) => {
+                        l.snapshot_tx_interval = snapshot_tx_interval;
+                        l.ledger_chunk_bytes = ledger_chunk_bytes.to_owned();
+                        StoreConfig::Lskv(l)
+                    }
+                    StoreConfig::Etcd(_) => todo!(),
+                };
+                let config = Config {
+                    store_config,
+                    rate,
+                    total: rate * 10,
+                    workload: YcsbWorkload::A,
+                    nodes: 3,
+                    tmpfs: false,
+                    max_clients: Some(100),
+                    measure_recovery: true,
                 };
                 configs.push(config);
             }
@@ -163,7 +249,8 @@ impl Experiment for YcsbExperiment {
                 .map(|i| format!("local://127.0.0.1:{}", 8000 + (i * 3)))
                 .collect()
         };
-        let (mut store, leader_address) = match &configuration.store_config {
+        let metrics_nodes = nodes.clone();
+        let (mut store, leader_address, node_pids) = match &configuration.store_config {
             StoreConfig::Lskv(config) => {
                 let store_config = LskvStore {
                     config: config.clone(),
@@ -172,11 +259,13 @@ impl Experiment for YcsbExperiment {
                     workspace: workspace.clone(),
                     http_version: 2,
                     tmpfs: configuration.tmpfs,
+                    require_version: self.require_version.clone(),
                 };
                 let store = store_config.run(&self.root_dir);
-                store_config.wait_for_ready().await;
+                let version = store_config.wait_for_ready().await;
+                write_version_csv(&configuration_dir, &version);
                 let leader_address = store_config.get_leader_address();
-                (store, leader_address)
+                (store, leader_address, store_config.node_pids())
             }
             StoreConfig::Etcd(config) => {
                 let store_config = EtcdStore {
@@ -185,14 +274,23 @@ impl Experiment for YcsbExperiment {
                     configuration_dir: configuration_dir.clone(),
                     workspace: workspace.clone(),
                     tmpfs: configuration.tmpfs,
+                    require_version: self.require_version.clone(),
                 };
                 let store = store_config.run(&self.root_dir);
-                store_config.wait_for_ready().await;
+                let version = store_config.wait_for_ready().await;
+                write_version_csv(&configuration_dir, &version);
                 let leader_address = store_config.get_leader_address(&self.root_dir);
-                (store, leader_address)
+                (store, leader_address, store_config.node_pids())
             }
         };
 
+        let metrics_scraper = MetricsScraper::start(
+            &configuration.store_config,
+            &metrics_nodes,
+            &configuration_dir,
+            METRICS_SCRAPE_INTERVAL,
+        );
+
         let load_path = configuration_dir
             .join("load.csv")
             .to_string_lossy()
@@ -255,6 +353,14 @@ impl Experiment for YcsbExperiment {
             )
             .await;
 
+        // Start profiling now, not right after readiness: the load container above just
+        // finished the data-population phase, so starting any earlier would capture that phase's
+        // CPU time mixed in with the measured run the flamegraph is meant to explain.
+        let profiler = match self.profile {
+            ProfileMode::None => None,
+            ProfileMode::Flamegraph => FlamegraphProfiler::start(&configuration_dir, &node_pids),
+        };
+
         debug!("Launching ycsb run container");
         let mut bench_command = configuration.to_command(&self.root_dir, &leader_address);
         bench_command.append(&mut vec![
@@ -296,6 +402,10 @@ impl Experiment for YcsbExperiment {
             .next()
             .await;
 
+        if let Some(profiler) = profiler {
+            profiler.stop_and_render(&configuration_dir).await;
+        }
+
         let _ = docker_runner
             .docker_client()
             .remove_container(
@@ -307,16 +417,57 @@ impl Experiment for YcsbExperiment {
             )
             .await;
 
-        nix::sys::signal::kill(
-            nix::unistd::Pid::from_raw(store.id() as i32),
-            nix::sys::signal::Signal::SIGINT,
-        )
-        .expect("cannot send ctrl-c");
-        let result = store.wait().unwrap();
-        if result.success() {
+        metrics_scraper.stop().await;
+
+        if configuration.measure_recovery {
+            debug!("Measuring crash-recovery time");
+            store.kill_and_wait(nix::sys::signal::Signal::SIGKILL);
+
+            let recovery_start = std::time::Instant::now();
+            let (restarted_store, recovery_ms) = match &configuration.store_config {
+                StoreConfig::Lskv(config) => {
+                    let store_config = LskvStore {
+                        config: config.clone(),
+                        nodes: metrics_nodes.clone(),
+                        configuration_dir: configuration_dir.clone(),
+                        workspace: workspace.clone(),
+                        http_version: 2,
+                        tmpfs: configuration.tmpfs,
+                        require_version: self.require_version.clone(),
+                    };
+                    let restarted_store = store_config.run(&self.root_dir);
+                    let version = store_config.wait_for_ready().await;
+                    let recovery_ms = recovery_start.elapsed().as_millis();
+                    write_version_csv(&configuration_dir, &version);
+                    let _leader_address = store_config.get_leader_address();
+                    (restarted_store, recovery_ms)
+                }
+                StoreConfig::Etcd(config) => {
+                    let store_config = EtcdStore {
+                        config: config.clone(),
+                        nodes: metrics_nodes.clone(),
+                        configuration_dir: configuration_dir.clone(),
+                        workspace: workspace.clone(),
+                        tmpfs: configuration.tmpfs,
+                        require_version: self.require_version.clone(),
+                    };
+                    let restarted_store = store_config.run(&self.root_dir);
+                    let version = store_config.wait_for_ready().await;
+                    let recovery_ms = recovery_start.elapsed().as_millis();
+                    write_version_csv(&configuration_dir, &version);
+                    let _leader_address = store_config.get_leader_address(&self.root_dir);
+                    (restarted_store, recovery_ms)
+                }
+            };
+            let (ledger_chunks, snapshots) = count_ledger_artifacts(&workspace);
+            write_recovery_csv(&configuration_dir, recovery_ms, ledger_chunks, snapshots);
+            store = restarted_store;
+        }
+
+        if store.shutdown(SHUTDOWN_TIMEOUT).await {
             Ok(())
         } else {
-            Err("Failed to run cluster".into())
+            Err("cluster did not shut down gracefully".into())
         }
     }
 
@@ -369,6 +520,74 @@ impl Experiment for YcsbExperiment {
                     all_data_opt = Some(config_and_results_data.lazy());
                 }
             }
+
+            for metrics_file in node_metrics_files(config_dir) {
+                info!(?metrics_file, "Loading node metrics");
+                let mut schema = Schema::new();
+                schema.with_column("member_id".into(), DataType::UInt64);
+                let metrics_data = CsvReader::from_path(&metrics_file)
+                    .unwrap()
+                    .has_header(true)
+                    .with_dtypes(Some(Arc::new(schema)))
+                    .finish()
+                    .unwrap();
+
+                let config_and_metrics_data =
+                    config_data.cross_join(&metrics_data, None, None).unwrap();
+
+                if let Some(all_data) = all_data_opt {
+                    all_data_opt = Some(
+                        diag_concat_lf([all_data, config_and_metrics_data.lazy()], true, true)
+                            .unwrap(),
+                    );
+                } else {
+                    all_data_opt = Some(config_and_metrics_data.lazy());
+                }
+            }
+
+            let recovery_file = config_dir.join("recovery.csv");
+            if recovery_file.is_file() {
+                info!(?recovery_file, "Loading recovery measurement");
+                let recovery_data = CsvReader::from_path(recovery_file)
+                    .unwrap()
+                    .has_header(true)
+                    .finish()
+                    .unwrap();
+
+                let config_and_recovery_data =
+                    config_data.cross_join(&recovery_data, None, None).unwrap();
+
+                if let Some(all_data) = all_data_opt {
+                    all_data_opt = Some(
+                        diag_concat_lf([all_data, config_and_recovery_data.lazy()], true, true)
+                            .unwrap(),
+                    );
+                } else {
+                    all_data_opt = Some(config_and_recovery_data.lazy());
+                }
+            }
+
+            let version_file = config_dir.join("server_version.csv");
+            if version_file.is_file() {
+                info!(?version_file, "Loading detected server version");
+                let version_data = CsvReader::from_path(version_file)
+                    .unwrap()
+                    .has_header(true)
+                    .finish()
+                    .unwrap();
+
+                let config_and_version_data =
+                    config_data.cross_join(&version_data, None, None).unwrap();
+
+                if let Some(all_data) = all_data_opt {
+                    all_data_opt = Some(
+                        diag_concat_lf([all_data, config_and_version_data.lazy()], true, true)
+                            .unwrap(),
+                    );
+                } else {
+                    all_data_opt = Some(config_and_version_data.lazy());
+                }
+            }
         }
         let mut csv_file = File::create(all_results_file).unwrap();
         if let Some(all_data) = all_data_opt {
@@ -379,6 +598,87 @@ impl Experiment for YcsbExperiment {
     }
 }
 
+/// A single crash-recovery measurement: wall-clock time to become ready again after a SIGKILL,
+/// plus how many ledger chunks and snapshots the node had to replay/load to get there.
+#[derive(Debug, serde::Serialize)]
+struct RecoverySample {
+    recovery_ms: u128,
+    ledger_chunks: usize,
+    snapshots: usize,
+}
+
+fn write_recovery_csv(
+    configuration_dir: &Path,
+    recovery_ms: u128,
+    ledger_chunks: usize,
+    snapshots: usize,
+) {
+    let path = configuration_dir.join("recovery.csv");
+    let mut writer = csv::Writer::from_path(path).unwrap();
+    writer
+        .serialize(RecoverySample {
+            recovery_ms,
+            ledger_chunks,
+            snapshots,
+        })
+        .unwrap();
+    writer.flush().unwrap();
+}
+
+/// Record the server build an experiment was measured against, so results can be tagged with
+/// the exact version the readiness handshake detected.
+fn write_version_csv(configuration_dir: &Path, version: &impl serde::Serialize) {
+    let path = configuration_dir.join("server_version.csv");
+    let mut writer = csv::Writer::from_path(path).unwrap();
+    writer.serialize(version).unwrap();
+    writer.flush().unwrap();
+}
+
+/// Count the ledger chunk and snapshot files present under `workspace`, recursing into node
+/// directories.
+fn count_ledger_artifacts(workspace: &Path) -> (usize, usize) {
+    fn visit(dir: &Path, ledger_chunks: &mut usize, snapshots: &mut usize) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            if path.is_dir() {
+                visit(&path, ledger_chunks, snapshots);
+            } else if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                if name.contains("snapshot") {
+                    *snapshots += 1;
+                } else if name.contains("ledger") {
+                    *ledger_chunks += 1;
+                }
+            }
+        }
+    }
+    let mut ledger_chunks = 0;
+    let mut snapshots = 0;
+    visit(workspace, &mut ledger_chunks, &mut snapshots);
+    (ledger_chunks, snapshots)
+}
+
+/// List the per-node metrics CSVs (`metrics-{member_id}.csv`) written by the [`MetricsScraper`]
+/// for a configuration, if any were written.
+fn node_metrics_files(config_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(config_dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_stem()
+                .and_then(|s| s.to_str())
+                .map(|s| s.starts_with("metrics-"))
+                .unwrap_or(false)
+                && path.extension().and_then(|e| e.to_str()) == Some("csv")
+        })
+        .collect()
+}
+
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct Config {
     rate: u32,
@@ -387,6 +687,9 @@ pub struct Config {
     workload: YcsbWorkload,
     nodes: usize,
     tmpfs: bool,
+    /// Whether to SIGKILL and restart the store after the load+bench phases to measure
+    /// crash-recovery time, recording the result to `recovery.csv`.
+    measure_recovery: bool,
     #[serde(flatten)]
     store_config: StoreConfig,
 }
@@ -459,10 +762,142 @@ pub enum YcsbWorkload {
     D,
     E,
     F,
+    /// Watch/notification-latency workload: `watchers` persistent streams subscribe to the whole
+    /// keyspace once, independent of the writer path, while ops sampled at `watch_weight` put
+    /// zipfian-distributed keys at `--rate`; each watch op reports the average put-to-notification
+    /// latency of the events the persistent streams delivered since the previous watch op, not a
+    /// separate latency per event.
+    Watch {
+        watchers: u32,
+    },
+    /// Transaction/batch workload: each operation bundles `txn_size` keys into a single txn
+    /// containing a mix of compares and puts/gets, with `txn_read_ratio` controlling the
+    /// fraction of pure reads and `txn_abort_on_conflict` controlling whether a failed compare
+    /// aborts the txn outright or retries.
+    Txn {
+        txn_size: u32,
+        txn_read_ratio: f32,
+        txn_abort_on_conflict: bool,
+    },
+    /// A user-supplied workload loaded from `--workloads-file`, rather than one of the textbook
+    /// A-F mixes.
+    Custom(WorkloadSpec),
+}
+
+/// A single named workload definition, as loaded from a `--workloads-file` TOML/JSON document.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct WorkloadSpec {
+    pub name: String,
+    #[serde(default)]
+    pub read_weight: u32,
+    #[serde(default)]
+    pub update_weight: u32,
+    #[serde(default)]
+    pub insert_weight: u32,
+    #[serde(default)]
+    pub scan_weight: u32,
+    #[serde(default)]
+    pub rmw_weight: u32,
+    #[serde(default)]
+    pub txn_weight: u32,
+    #[serde(default)]
+    pub txn_size: Option<u32>,
+    #[serde(default)]
+    pub txn_read_ratio: Option<f32>,
+    #[serde(default)]
+    pub txn_abort_on_conflict: Option<bool>,
+    #[serde(default = "default_request_distribution")]
+    pub request_distribution: String,
+    #[serde(default)]
+    pub max_scan_length: Option<u32>,
+    #[serde(default)]
+    pub zipfian_constant: Option<f64>,
+}
+
+fn default_request_distribution() -> String {
+    "zipfian".to_owned()
+}
+
+/// Load a list of [`WorkloadSpec`]s from a TOML or JSON file, dispatching on its extension.
+pub fn load_workloads_file(path: &Path) -> Vec<WorkloadSpec> {
+    let contents = fs::read_to_string(path).unwrap();
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => serde_json::from_str(&contents).unwrap(),
+        _ => toml::from_str(&contents).unwrap(),
+    }
 }
 
 impl YcsbWorkload {
     fn to_command(&self) -> Vec<String> {
+        if let Self::Watch { watchers } = self {
+            return vec![
+                "--watch-weight".to_owned(),
+                "1".to_owned(),
+                "--watchers".to_owned(),
+                watchers.to_string(),
+                "--request-distribution".to_owned(),
+                "zipfian".to_owned(),
+            ];
+        }
+        if let Self::Txn {
+            txn_size,
+            txn_read_ratio,
+            txn_abort_on_conflict,
+        } = self
+        {
+            let mut args = vec![
+                "--txn-weight".to_owned(),
+                "1".to_owned(),
+                "--txn-size".to_owned(),
+                txn_size.to_string(),
+                "--txn-read-ratio".to_owned(),
+                txn_read_ratio.to_string(),
+                "--request-distribution".to_owned(),
+                "zipfian".to_owned(),
+            ];
+            if *txn_abort_on_conflict {
+                args.push("--txn-abort-on-conflict".to_owned());
+            }
+            return args;
+        }
+        if let Self::Custom(spec) = self {
+            let mut args = vec![
+                "--read-weight".to_owned(),
+                spec.read_weight.to_string(),
+                "--update-weight".to_owned(),
+                spec.update_weight.to_string(),
+                "--insert-weight".to_owned(),
+                spec.insert_weight.to_string(),
+                "--scan-weight".to_owned(),
+                spec.scan_weight.to_string(),
+                "--rmw-weight".to_owned(),
+                spec.rmw_weight.to_string(),
+                "--txn-weight".to_owned(),
+                spec.txn_weight.to_string(),
+                "--request-distribution".to_owned(),
+                spec.request_distribution.clone(),
+            ];
+            if let Some(max_scan_length) = spec.max_scan_length {
+                args.push("--max-scan-length".to_owned());
+                args.push(max_scan_length.to_string());
+            }
+            if let Some(zipfian_constant) = spec.zipfian_constant {
+                args.push("--zipfian-theta".to_owned());
+                args.push(zipfian_constant.to_string());
+            }
+            if let Some(txn_size) = spec.txn_size {
+                args.push("--txn-size".to_owned());
+                args.push(txn_size.to_string());
+            }
+            if let Some(txn_read_ratio) = spec.txn_read_ratio {
+                args.push("--txn-read-ratio".to_owned());
+                args.push(txn_read_ratio.to_string());
+            }
+            if spec.txn_abort_on_conflict == Some(true) {
+                args.push("--txn-abort-on-conflict".to_owned());
+            }
+            return args;
+        }
         let args = match self {
             Self::A => vec![
                 "--read-weight",
@@ -507,6 +942,9 @@ impl YcsbWorkload {
                 "--request-distribution",
                 "zipfian",
             ],
+            Self::Watch { .. } | Self::Txn { .. } | Self::Custom(_) => {
+                unreachable!("handled above")
+            }
         };
         args.into_iter().map(|i| i.to_owned()).collect()
     }